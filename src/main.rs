@@ -1,18 +1,23 @@
 use aws_config::{defaults, BehaviorVersion};
 use aws_config::meta::region::RegionProviderChain;
-use aws_sdk_route53::{config::Region, types::{HostedZone, RrType, ChangeBatch, ChangeAction, Change, ResourceRecord, ResourceRecordSet}, Client};
+use aws_sdk_route53::{config::Region, types::{RrType, ChangeBatch, ChangeAction, ChangeStatus, Change, ResourceRecord, ResourceRecordSet}, Client};
 
 use clap::Parser;
 
 use env_logger::{Builder, Env};
-use log::{info, debug};
+use log::{info, debug, error};
 
 use reqwest;
 
 use serde::Deserialize;
+use serde_json;
+use serde_yaml;
+use toml;
 
+use std::collections::HashMap;
 use std::io::{Error as ioError, ErrorKind};
 use std::error::Error as Error;
+use std::net::IpAddr;
 
 
 const IP_SERVICE: &str = "http://httpbin.org/ip";
@@ -24,76 +29,215 @@ struct Opt {
     #[structopt(short, long)]
     region: Option<String>,
 
-    /// The hosted zone domain to update
+    /// The hosted zone domain to update. Required unless --config is given.
     #[structopt(short, long)]
-    domain: String,
+    domain: Option<String>,
+
+    /// Subdomain to update. Required unless --config is given.
+    #[structopt(short, long)]
+    subdomain: Option<String>,
 
-    /// Subdomain to update
+    /// Run as a long-lived daemon instead of exiting after a single check.
     #[structopt(short, long)]
-    subdomain: String
+    watch: bool,
+
+    /// Polling interval, in seconds, between checks when running with --watch.
+    #[structopt(long, default_value = "300")]
+    interval: u64,
+
+    /// Wait for the Route53 change to propagate (reach INSYNC) before returning.
+    #[structopt(long)]
+    wait: bool,
+
+    /// Maximum time, in seconds, to wait for change propagation when using --wait.
+    #[structopt(long, default_value = "120")]
+    timeout: u64,
+
+    /// Interval, in seconds, between propagation status checks when using --wait.
+    #[structopt(long, default_value = "5")]
+    poll_interval: u64,
+
+    /// Custom IP-source URL to try before the built-in fallback chain (plain-text response expected).
+    #[structopt(long)]
+    ip_source: Option<String>,
+
+    /// DNS record type(s) to manage: "A", "AAAA", or "both".
+    #[structopt(long, default_value = "A")]
+    record_type: String,
+
+    /// TTL, in seconds, to set on managed resource records.
+    #[structopt(long, default_value = "300")]
+    ttl: i64,
+
+    /// Path to a TOML or YAML config file listing multiple records to keep in sync, instead of
+    /// the single --domain/--subdomain pair. When given, --domain and --subdomain are ignored.
+    #[structopt(long)]
+    config: Option<String>
+}
+
+/// A single record entry in a `--config` file.
+#[derive(Debug, Deserialize, Clone)]
+struct RecordConfig {
+    domain: String,
+    subdomain: String,
+    #[serde(default = "default_record_type")]
+    record_type: String,
+    ttl: Option<i64>
+}
+
+fn default_record_type() -> String {
+    String::from("A")
+}
+
+/// Top-level shape of a `--config` file.
+#[derive(Debug, Deserialize)]
+struct BatchConfig {
+    records: Vec<RecordConfig>
 }
 
-/// External IP address, as sourced from httpbin.org.
+/// Loads the records listed in a `--config` file, parsing it as YAML if its extension is
+/// `.yaml`/`.yml` and as TOML otherwise.
+fn load_batch_config(path: &str) -> Result<Vec<RecordConfig>, Box<dyn Error>> {
+    let contents = std::fs::read_to_string(path)?;
+
+    let config: BatchConfig = if path.ends_with(".yaml") || path.ends_with(".yml") {
+        serde_yaml::from_str(&contents)?
+    } else {
+        toml::from_str(&contents)?
+    };
+
+    Ok(config.records)
+}
+
+/// Response body shape for httpbin-style JSON IP-echo services.
 #[derive(Deserialize, Debug)]
-struct ExternalIp {
+struct JsonIpResponse {
     origin: String
 }
 
+/// The response format an IP-source endpoint uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IpSourceFormat {
+    /// httpbin-style JSON body: `{ "origin": "1.2.3.4" }`.
+    Json,
+    /// Plain-text body containing just the address, optionally an `X-Forwarded-For`-style
+    /// comma-separated list (the first entry is used).
+    PlainText
+}
 
-/// Get the external IP of the current network.
-async fn get_external_ip() -> Result<ExternalIp, Box<dyn Error>> {
-    let ip = reqwest::get(IP_SERVICE)
-        .await?
-        .json::<ExternalIp>()
-        .await?;
-    info!("Got external IP address {}", ip.origin);
-    Ok(ip)
+/// A single external-IP provider: an endpoint URL and the format of its response body.
+#[derive(Debug, Clone)]
+struct IpSource {
+    url: String,
+    format: IpSourceFormat
 }
 
-/// Get HostedZone info from AWS Route53.
-async fn parse_host_info(client: &aws_sdk_route53::Client) -> Result<Vec<HostedZone>, aws_sdk_route53::Error> {
-    let hosted_zone_count = client.get_hosted_zone_count().send().await?;
-    let mut hosted_zones_vec = Vec::new();
+impl IpSource {
+    fn new(url: &str, format: IpSourceFormat) -> Self {
+        IpSource { url: String::from(url), format }
+    }
+}
 
-    info!(
-        "Number of hosted zones in region : {}",
-        hosted_zone_count.hosted_zone_count(),
-    );
+/// Built-in fallback chain of IP-source providers, tried in order until one succeeds.
+fn default_ip_sources() -> Vec<IpSource> {
+    vec![
+        IpSource::new(IP_SERVICE, IpSourceFormat::Json),
+        IpSource::new("https://ifconfig.me/ip", IpSourceFormat::PlainText),
+        IpSource::new("https://icanhazip.com", IpSourceFormat::PlainText)
+    ]
+}
 
-    let hosted_zones = client.list_hosted_zones().send().await?;
+/// Built-in fallback chain of IPv6-capable IP-source providers, tried in order until one succeeds.
+fn default_ipv6_sources() -> Vec<IpSource> {
+    vec![
+        IpSource::new("https://api6.ipify.org", IpSourceFormat::PlainText),
+        IpSource::new("https://v6.ident.me", IpSourceFormat::PlainText)
+    ]
+}
 
-    info!("Zones:");
+/// Parses the `--record-type` option into the concrete Route53 record type(s) to manage.
+fn parse_record_types(record_type: &str) -> Result<Vec<RrType>, Box<dyn Error>> {
+    match record_type.to_uppercase().as_str() {
+        "A" => Ok(vec![RrType::A]),
+        "AAAA" => Ok(vec![RrType::Aaaa]),
+        "BOTH" => Ok(vec![RrType::A, RrType::Aaaa]),
+        other => Err(Box::new(ioError::new(ErrorKind::InvalidInput, format!("Invalid --record-type {}, expected A, AAAA, or both", other))))
+    }
+}
 
-    for hz in hosted_zones.hosted_zones() {
-        let zone_name = hz.name();
-        let zone_id = hz.id();
+/// Parses a provider's raw response body into a concrete IP address, rejecting anything that
+/// isn't a well-formed address rather than writing it blindly into a resource record.
+fn parse_ip_response(body: &str, format: IpSourceFormat) -> Result<IpAddr, Box<dyn Error>> {
+    match format {
+        IpSourceFormat::Json => {
+            let parsed: JsonIpResponse = serde_json::from_str(body)?;
+            Ok(parsed.origin.trim().parse::<IpAddr>()?)
+        }
+        IpSourceFormat::PlainText => {
+            let first = body.trim().split(',').next().unwrap_or("").trim();
+            Ok(first.parse::<IpAddr>()?)
+        }
+    }
+}
 
-        info!("  ID :   {}", zone_id);
-        info!("  Name : {}", zone_name);
+/// Fetches and parses the external IP from a single source.
+async fn fetch_ip_from(source: &IpSource) -> Result<IpAddr, Box<dyn Error>> {
+    let body = reqwest::get(&source.url).await?.text().await?;
+    parse_ip_response(&body, source.format)
+}
 
-        hosted_zones_vec.push(hz.clone());
+/// Gets the external IP of the current network by trying each source in order, returning the
+/// first that yields a valid address. A source that's unreachable or returns a malformed
+/// response is logged and skipped rather than aborting the whole lookup.
+async fn get_external_ip(sources: &[IpSource]) -> Result<IpAddr, Box<dyn Error>> {
+    let mut last_err: Option<Box<dyn Error>> = None;
+
+    for source in sources {
+        match fetch_ip_from(source).await {
+            Ok(ip) => {
+                info!("Got external IP address {} from {}", ip, source.url);
+                return Ok(ip);
+            }
+            Err(e) => {
+                error!("IP source {} failed, trying next: {}", source.url, e);
+                last_err = Some(e);
+            }
+        }
     }
 
-    Ok(hosted_zones_vec)
+    Err(last_err.unwrap_or_else(|| Box::new(ioError::new(ErrorKind::NotFound, "No IP sources configured"))))
 }
 
-/// Get HostedZone ID for a domain from HostedZone info.
-fn get_hosted_zone_id(hosted_zones: &Vec<HostedZone>, domain: &str) -> Result<String, ioError> {
-    for hosted_zone in hosted_zones {
-        if hosted_zone.name().contains(domain) {
-            return Ok(String::from(hosted_zone.id().split("/").nth(2).expect("Failed to parse hosted zone id.")));
-        }
+/// Look up the HostedZone ID for a domain via a targeted `list_hosted_zones_by_name` query,
+/// rather than enumerating every zone in the account. Route53 returns zones sorted from the
+/// queried `dns_name` onward, so the first result is checked for an exact name match instead of
+/// the substring match a naive scan would need (which would wrongly match e.g. `example.com`
+/// against `notexample.com`).
+async fn get_hosted_zone_id(client: &aws_sdk_route53::Client, domain: &str) -> Result<String, Box<dyn Error>> {
+    let dns_name = format!("{}.", domain.trim_end_matches('.'));
+
+    let response = client.list_hosted_zones_by_name()
+        .dns_name(&dns_name)
+        .send()
+        .await?;
+
+    let hosted_zone = response.hosted_zones().first()
+        .ok_or_else(|| ioError::new(ErrorKind::NotFound, format!("Hosted zone for domain {} not found", domain)))?;
+
+    if hosted_zone.name() != dns_name {
+        return Err(Box::new(ioError::new(ErrorKind::NotFound, format!("Hosted zone for domain {} not found", domain))));
     }
-    Err(ioError::new(ErrorKind::NotFound, "Hosted zone for domain not found"))
+
+    Ok(String::from(hosted_zone.id().split("/").nth(2).expect("Failed to parse hosted zone id.")))
 }
 
 /// Checks a HostedZone's resource records for the fully-qualified domain name, and checks if the external IP matches the resource configuration.
-async fn check_hosted_zone(client: &aws_sdk_route53::Client, hosted_zone_id: &str, external_ip: &str, domain: &str, subdomain: &str) -> Result<bool, Box<dyn Error>> {
+async fn check_hosted_zone(client: &aws_sdk_route53::Client, hosted_zone_id: &str, external_ip: &str, domain: &str, subdomain: &str, record_type: &RrType) -> Result<bool, Box<dyn Error>> {
     let full_domain = format!("{}.{}.", subdomain, domain);
     let request = client.list_resource_record_sets()
         .hosted_zone_id(hosted_zone_id)
         .start_record_name(&full_domain)
-        .start_record_type(RrType::A);
+        .start_record_type(record_type.clone());
     let response = request.send().await?;
     
     for resource_record_set in response.resource_record_sets {
@@ -113,31 +257,263 @@ async fn check_hosted_zone(client: &aws_sdk_route53::Client, hosted_zone_id: &st
     Err(Box::new(ioError::new(ErrorKind::NotFound, format!("ResourceRecordSet for domain {} not found.", full_domain))))
 }
 
-/// Updates the HostedZone resource with the external IP address.
-async fn update_hosted_zone(client: &aws_sdk_route53::Client, hosted_zone_id: &str, external_ip: &str, domain: &str, subdomain: &str) -> Result<(), Box<dyn Error>> {
+/// Builds a Route53 `Change` that upserts a single resource record set.
+fn build_upsert_change(domain: &str, subdomain: &str, record_type: &RrType, ttl: i64, external_ip: &str) -> Result<Change, Box<dyn Error>> {
     let full_domain = format!("{}.{}.", subdomain, domain);
+    Ok(Change::builder()
+        .action(ChangeAction::Upsert)
+        .resource_record_set(ResourceRecordSet::builder()
+            .name(full_domain)
+            .r#type(record_type.clone())
+            .ttl(ttl)
+            .resource_records(ResourceRecord::builder()
+                .value(external_ip)
+                .build()?)
+            .build()?)
+        .build()?)
+}
+
+/// Submits a `ChangeBatch` containing one or more changes to a hosted zone, returning the
+/// Route53 change ID so the caller can poll for propagation with `wait_for_change`.
+async fn submit_change_batch(client: &aws_sdk_route53::Client, hosted_zone_id: &str, changes: Vec<Change>) -> Result<String, Box<dyn Error>> {
     let request = client.change_resource_record_sets()
         .hosted_zone_id(hosted_zone_id)
         .change_batch(ChangeBatch::builder()
-            .changes(Change::builder()
-                .action(ChangeAction::Upsert)
-                .resource_record_set(ResourceRecordSet::builder()
-                    .name(full_domain)
-                    .r#type(RrType::A)
-                    .ttl(300)
-                    .resource_records(ResourceRecord::builder()
-                        .value(external_ip)
-                        .build()?)
-                    .build()?)
-                .build()?)
+            .set_changes(Some(changes))
             .build()?);
-    debug!("Request: {:?}", request); 
+    debug!("Request: {:?}", request);
     let response = request.send().await?;
     debug!("Response: {:?}", response);
 
+    let change_info = response.change_info()
+        .ok_or_else(|| ioError::new(ErrorKind::Other, "change_resource_record_sets response did not contain ChangeInfo"))?;
+
+    Ok(String::from(change_info.id()))
+}
+
+/// Updates the HostedZone resource with the external IP address. Returns the Route53 change ID
+/// so the caller can poll for propagation with `wait_for_change`.
+async fn update_hosted_zone(client: &aws_sdk_route53::Client, hosted_zone_id: &str, external_ip: &str, domain: &str, subdomain: &str, record_type: &RrType, ttl: i64) -> Result<String, Box<dyn Error>> {
+    let change = build_upsert_change(domain, subdomain, record_type, ttl, external_ip)?;
+    submit_change_batch(client, hosted_zone_id, vec![change]).await
+}
+
+/// Minimal wrapper around the one Route53 call `wait_for_change` needs, so the polling loop can
+/// be unit-tested against a fake implementation instead of a live client.
+#[async_trait::async_trait]
+trait ChangeStatusSource {
+    async fn get_change_status(&self, change_id: &str) -> Result<ChangeStatus, Box<dyn Error>>;
+}
+
+#[async_trait::async_trait]
+impl ChangeStatusSource for aws_sdk_route53::Client {
+    async fn get_change_status(&self, change_id: &str) -> Result<ChangeStatus, Box<dyn Error>> {
+        let response = self.get_change().id(change_id).send().await?;
+        let status = response.change_info()
+            .ok_or_else(|| ioError::new(ErrorKind::Other, "get_change response did not contain ChangeInfo"))?
+            .status()
+            .clone();
+        Ok(status)
+    }
+}
+
+/// Polls Route53 for a change's propagation status until it reaches `INSYNC` or `timeout`
+/// seconds elapse. Kept separate from `update_hosted_zone` so it can be unit-tested on its own.
+async fn wait_for_change<C: ChangeStatusSource>(client: &C, change_id: &str, poll_interval: u64, timeout: u64) -> Result<(), Box<dyn Error>> {
+    let change_id = change_id.trim_start_matches("/change/");
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout);
+
+    loop {
+        let status = client.get_change_status(change_id).await?;
+        info!("Change {} status: {:?}", change_id, status);
+
+        if status == ChangeStatus::Insync {
+            return Ok(());
+        }
+
+        if std::time::Instant::now() >= deadline {
+            return Err(Box::new(ioError::new(ErrorKind::TimedOut, format!("Timed out waiting for change {} to reach INSYNC", change_id))));
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(poll_interval)).await;
+    }
+}
+
+#[cfg(test)]
+mod wait_for_change_tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct FakeChangeStatusSource {
+        statuses: Vec<ChangeStatus>,
+        calls: AtomicUsize
+    }
+
+    impl FakeChangeStatusSource {
+        fn always(status: ChangeStatus) -> Self {
+            FakeChangeStatusSource { statuses: vec![status], calls: AtomicUsize::new(0) }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ChangeStatusSource for FakeChangeStatusSource {
+        async fn get_change_status(&self, _change_id: &str) -> Result<ChangeStatus, Box<dyn Error>> {
+            let index = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.statuses[index.min(self.statuses.len() - 1)].clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_ok_when_already_insync() {
+        let client = FakeChangeStatusSource::always(ChangeStatus::Insync);
+        let result = wait_for_change(&client, "/change/ABC123", 1, 5).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn times_out_when_never_insync() {
+        let client = FakeChangeStatusSource::always(ChangeStatus::Pending);
+        let result = wait_for_change(&client, "/change/ABC123", 1, 0).await;
+        assert!(result.is_err());
+    }
+}
+
+/// Caches the most recently applied address per (domain, subdomain, record type), so an
+/// unchanged IP for a record already confirmed in sync skips the `list_resource_record_sets`
+/// call, without masking a different record of the same type that hasn't been confirmed this tick.
+#[derive(Debug, Default)]
+struct IpCache {
+    applied: HashMap<String, String>
+}
+
+impl IpCache {
+    fn key(domain: &str, subdomain: &str, record_type: &RrType) -> String {
+        format!("{}.{}|{:?}", subdomain, domain, record_type)
+    }
+
+    fn get(&self, domain: &str, subdomain: &str, record_type: &RrType) -> Option<&String> {
+        self.applied.get(&Self::key(domain, subdomain, record_type))
+    }
+
+    fn set(&mut self, domain: &str, subdomain: &str, record_type: &RrType, ip: String) {
+        self.applied.insert(Self::key(domain, subdomain, record_type), ip);
+    }
+}
+
+/// Runs a single check-and-update iteration for one record type, propagating errors rather than
+/// swallowing them; it's up to the caller (the `--watch` loop vs. a one-shot run) to decide
+/// whether to log-and-continue or exit non-zero.
+async fn run_tick_for_record_type(client: &aws_sdk_route53::Client, hosted_zone_id: &str, domain: &str, subdomain: &str, record_type: &RrType, ttl: i64, ip_sources: &[IpSource], ip_cache: &mut IpCache, wait: bool, timeout: u64, poll_interval: u64) -> Result<(), Box<dyn Error>> {
+    let external_ip = get_external_ip(ip_sources).await?.to_string();
+
+    if ip_cache.get(domain, subdomain, record_type).map(String::as_str) == Some(external_ip.as_str()) {
+        debug!("External IP {} unchanged for {:?} record since last check, skipping.", external_ip, record_type);
+        return Ok(());
+    }
+
+    let needs_update = check_hosted_zone(client, hosted_zone_id, &external_ip, domain, subdomain, record_type).await?;
+
+    if needs_update {
+        let change_id = update_hosted_zone(client, hosted_zone_id, &external_ip, domain, subdomain, record_type, ttl).await?;
+
+        if wait {
+            wait_for_change(client, &change_id, poll_interval, timeout).await?;
+        }
+    }
+
+    ip_cache.set(domain, subdomain, record_type, external_ip);
     Ok(())
+}
 
-} 
+/// Runs a check-and-update iteration across every configured record type, picking the
+/// IPv4 or IPv6 source chain appropriate for each. Stops at the first record type that fails
+/// and propagates its error; see `run_tick_for_record_type` for why errors aren't swallowed here.
+async fn run_tick(client: &aws_sdk_route53::Client, hosted_zone_id: &str, domain: &str, subdomain: &str, record_types: &[RrType], ttl: i64, ipv4_sources: &[IpSource], ipv6_sources: &[IpSource], ip_cache: &mut IpCache, wait: bool, timeout: u64, poll_interval: u64) -> Result<(), Box<dyn Error>> {
+    for record_type in record_types {
+        let ip_sources = if record_type == &RrType::Aaaa { ipv6_sources } else { ipv4_sources };
+        run_tick_for_record_type(client, hosted_zone_id, domain, subdomain, record_type, ttl, ip_sources, ip_cache, wait, timeout, poll_interval).await?;
+    }
+    Ok(())
+}
+
+/// Runs one config-driven batch update, grouping records that need changing into one
+/// `ChangeBatch` per hosted zone; a malformed or unresolvable record is logged and skipped
+/// rather than aborting the whole batch.
+async fn run_batch(client: &aws_sdk_route53::Client, records: &[RecordConfig], ipv4_sources: &[IpSource], ipv6_sources: &[IpSource], ip_cache: &mut IpCache, wait: bool, timeout: u64, poll_interval: u64) -> Result<(), Box<dyn Error>> {
+    let ipv4 = get_external_ip(ipv4_sources).await;
+    let ipv6 = get_external_ip(ipv6_sources).await;
+
+    let mut changes_by_zone: HashMap<String, Vec<Change>> = HashMap::new();
+    let mut pending_cache_updates: HashMap<String, Vec<(String, String, RrType, String)>> = HashMap::new();
+
+    for record in records {
+        let record_types = match parse_record_types(&record.record_type) {
+            Ok(record_types) => record_types,
+            Err(e) => {
+                error!("Skipping {}.{}: invalid record_type: {}", record.subdomain, record.domain, e);
+                continue;
+            }
+        };
+        let ttl = record.ttl.unwrap_or(300);
+
+        let hosted_zone_id = match get_hosted_zone_id(client, &record.domain).await {
+            Ok(hosted_zone_id) => hosted_zone_id,
+            Err(e) => {
+                error!("Skipping {}.{}: failed to resolve hosted zone: {}", record.subdomain, record.domain, e);
+                continue;
+            }
+        };
+
+        for record_type in &record_types {
+            let external_ip = match (record_type, &ipv4, &ipv6) {
+                (RrType::Aaaa, _, Ok(ip)) => ip.to_string(),
+                (record_type, Ok(ip), _) if record_type != &RrType::Aaaa => ip.to_string(),
+                _ => {
+                    error!("Skipping {:?} record for {}.{}: no external IP available", record_type, record.subdomain, record.domain);
+                    continue;
+                }
+            };
+
+            if ip_cache.get(&record.domain, &record.subdomain, record_type).map(String::as_str) == Some(external_ip.as_str()) {
+                debug!("External IP {} unchanged for {:?} record, skipping {}.{}.", external_ip, record_type, record.subdomain, record.domain);
+                continue;
+            }
+
+            let needs_update = match check_hosted_zone(client, &hosted_zone_id, &external_ip, &record.domain, &record.subdomain, record_type).await {
+                Ok(needs_update) => needs_update,
+                Err(e) => {
+                    error!("Skipping {:?} record for {}.{}: {}", record_type, record.subdomain, record.domain, e);
+                    continue;
+                }
+            };
+
+            if !needs_update {
+                debug!("{:?} record for {}.{} is already up-to-date, omitting from batch.", record_type, record.subdomain, record.domain);
+                ip_cache.set(&record.domain, &record.subdomain, record_type, external_ip);
+                continue;
+            }
+
+            let change = build_upsert_change(&record.domain, &record.subdomain, record_type, ttl, &external_ip)?;
+            changes_by_zone.entry(hosted_zone_id.clone()).or_default().push(change);
+            pending_cache_updates.entry(hosted_zone_id.clone()).or_default()
+                .push((record.domain.clone(), record.subdomain.clone(), record_type.clone(), external_ip));
+        }
+    }
+
+    for (hosted_zone_id, changes) in changes_by_zone {
+        let change_id = submit_change_batch(client, &hosted_zone_id, changes).await?;
+        if wait {
+            wait_for_change(client, &change_id, poll_interval, timeout).await?;
+        }
+        if let Some(updates) = pending_cache_updates.remove(&hosted_zone_id) {
+            for (domain, subdomain, record_type, ip) in updates {
+                ip_cache.set(&domain, &subdomain, &record_type, ip);
+            }
+        }
+    }
+
+    Ok(())
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -146,7 +522,15 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Builder::from_env(env).init();
 
     // configure AWS client
-    let Opt { region, domain, subdomain } = Opt::parse();
+    let Opt { region, domain, subdomain, watch, interval, wait, timeout, poll_interval, ip_source, record_type, ttl, config } = Opt::parse();
+
+    let mut ipv4_sources = Vec::new();
+    if let Some(url) = &ip_source {
+        ipv4_sources.push(IpSource::new(url, IpSourceFormat::PlainText));
+    }
+    ipv4_sources.extend(default_ip_sources());
+
+    let ipv6_sources = default_ipv6_sources();
 
     let region_provider = RegionProviderChain::first_try(region.map(Region::new))
         .or_default_provider()
@@ -157,16 +541,46 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .await;
     let client = Client::new(&shared_config);
 
-    let external_ip = get_external_ip().await?;
-    let hosted_zones: Vec<HostedZone> = parse_host_info(&client).await?;
-    
-    let hosted_zone_id = get_hosted_zone_id(&hosted_zones, &domain)?;
-    info!("Hosted zone id: {}", hosted_zone_id);
+    if let Some(config_path) = config {
+        let records = load_batch_config(&config_path)?;
+        let mut ip_cache = IpCache::default();
+
+        if watch {
+            info!("Running in watch mode with config {}, polling every {} seconds.", config_path, interval);
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval));
+            loop {
+                ticker.tick().await;
+                if let Err(e) = run_batch(&client, &records, &ipv4_sources, &ipv6_sources, &mut ip_cache, wait, timeout, poll_interval).await {
+                    error!("Batch update failed, will retry next tick: {}", e);
+                }
+            }
+        } else {
+            run_batch(&client, &records, &ipv4_sources, &ipv6_sources, &mut ip_cache, wait, timeout, poll_interval).await?;
+        }
+
+        return Ok(());
+    }
 
-    let needs_update = check_hosted_zone(&client, &hosted_zone_id, &external_ip.origin, &domain, &subdomain).await?;
+    let domain = domain.ok_or_else(|| ioError::new(ErrorKind::InvalidInput, "--domain is required unless --config is given"))?;
+    let subdomain = subdomain.ok_or_else(|| ioError::new(ErrorKind::InvalidInput, "--subdomain is required unless --config is given"))?;
+    let record_types = parse_record_types(&record_type)?;
 
-    if needs_update {
-        update_hosted_zone(&client, &hosted_zone_id, &external_ip.origin, &domain, &subdomain).await?
+    let hosted_zone_id = get_hosted_zone_id(&client, &domain).await?;
+    info!("Hosted zone id: {}", hosted_zone_id);
+
+    if watch {
+        info!("Running in watch mode, polling every {} seconds.", interval);
+        let mut ip_cache = IpCache::default();
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = run_tick(&client, &hosted_zone_id, &domain, &subdomain, &record_types, ttl, &ipv4_sources, &ipv6_sources, &mut ip_cache, wait, timeout, poll_interval).await {
+                error!("Tick failed, will retry next tick: {}", e);
+            }
+        }
+    } else {
+        let mut ip_cache = IpCache::default();
+        run_tick(&client, &hosted_zone_id, &domain, &subdomain, &record_types, ttl, &ipv4_sources, &ipv6_sources, &mut ip_cache, wait, timeout, poll_interval).await?;
     }
 
     Ok(())